@@ -1,6 +1,5 @@
 use std::{
   cmp::{max, min},
-  collections::HashSet,
   ffi::{CStr, c_void},
   ptr::null,
 };
@@ -9,18 +8,19 @@ use const_str::cstr;
 use num_traits::NumCast;
 use vapours::{enums::ColorRange, frame::VapoursVideoFrame, generic::HoldsVideoFormat};
 use vapoursynth4_rs::{
-  ColorFamily, SampleType,
+  ColorFamily, SampleType, VideoInfo,
   core::CoreRef,
   declare_plugin,
   ffi::{VSFrame, VSVideoFormat},
   frame::{Frame, FrameContext, VideoFormat, VideoFrame},
   key,
-  map::MapRef,
+  map::{AppendMode, MapRef, Value},
   node::{
     ActivationReason, Dependencies, Filter, FilterDependency, Node, RequestPattern, VideoNode,
   },
   utils::{is_constant_video_format, is_same_video_info},
 };
+use wide::{f32x4, u8x16, u16x8};
 
 fn is_8_to_16_or_float_format(format: &VSVideoFormat) -> bool {
   if format.color_family == ColorFamily::Undefined {
@@ -59,6 +59,32 @@ fn normalize_planes(input: &MapRef<'_>) -> Result<Vec<bool>, String> {
   Ok(process)
 }
 
+/// Selects how seeds and growth candidates are determined for a plane.
+enum Mode {
+  /// Seed from pixels marked in both `clipa` and `clipb`, grow through any
+  /// pixel marked in `clipb`. This is the classic two-clip Avisynth
+  /// `mt_hysteresis` behavior.
+  TwoClip,
+
+  /// Seed from pixels `>= th_hi` in `clipa`, grow through any pixel
+  /// `>= th_lo`. This is classic Canny-style hysteresis thresholding on a
+  /// single clip.
+  Threshold { th_lo: f64, th_hi: f64 },
+}
+
+/// Controls the output frame count when `clipa` and `clipb` have different
+/// lengths.
+enum Length {
+  /// Output the shorter of `clipa` and `clipb`.
+  Shortest,
+
+  /// Output the same number of frames as `clipa`.
+  ClipA,
+
+  /// Output the same number of frames as `clipb`.
+  ClipB,
+}
+
 /// Grows the mask in `clipa` (`node1`) into the mask in `clipb` (`node2`). This
 /// is an equivalent of the Avisynth function `mt_hysteresis`. Note that both
 /// clips are are expected to be in the typical mask range which means that all
@@ -74,56 +100,212 @@ fn normalize_planes(input: &MapRef<'_>) -> Result<Vec<bool>, String> {
 /// pixels is also marked in the corresponding plane from `clipa`. The argument
 /// `planes` controls which planes to process, with the default being all. Any
 /// unprocessed planes will be copied from the corresponding plane in `clipa`.
+///
+/// If `clipb` is omitted, `th_lo` and `th_hi` must be given instead and the
+/// filter performs hysteresis thresholding directly on `clipa`: pixels
+/// `>= th_hi` seed a component, and the component grows through any
+/// neighbouring pixel `>= th_lo`. The output is bi-level (0/peak) in either
+/// mode.
+///
+/// The output frame also carries `HysteresisComponentCount`,
+/// `HysteresisGrownArea`, and `HysteresisCoverage` properties summarizing the
+/// connected components found across the processed planes.
+///
+/// `clipa` and `clipb` may have different lengths; the `length` parameter
+/// (`"shortest"`, `"clipa"`, or `"clipb"`, default `"clipa"`) picks the output
+/// frame count. Requests for frame indices past the end of either input are
+/// clamped to its last frame rather than erroring.
 struct HysteresisFilter {
   node1: VideoNode,
-  node2: VideoNode,
+  node2: Option<VideoNode>,
 
   /// Peak value.
   peak: u32,
 
+  mode: Mode,
+
   /// Indicates whether or not the plane at index `i` should be processed.
   process_planes: Vec<bool>,
 }
 
+/// Clamps a requested frame index to the last valid frame of `node`, so that
+/// a clip shorter than the output is simply held on its last frame instead of
+/// producing an out-of-range request.
+fn clamp_frame(n: i32, node: &VideoNode) -> i32 {
+  n.min(node.info().num_frames - 1)
+}
+
+/// A per-pixel-offset predicate used while flood-filling a plane.
+type Predicate<'a> = Box<dyn Fn(usize) -> bool + 'a>;
+
+/// Scans a plane for hysteresis seed pixels using SIMD lanes, appending their
+/// offsets to `out`. This only covers the seed-detection pass: the flood fill
+/// itself is data-dependent and stays scalar.
+trait SeedScan: Copy + PartialOrd {
+  fn scan_two_clip(src1: &[Self], src2: &[Self], lower: Self, out: &mut Vec<usize>);
+  fn scan_threshold(src1: &[Self], th_hi: Self, out: &mut Vec<usize>);
+}
+
+macro_rules! impl_seed_scan {
+  ($t:ty, $simd:ty, $lanes:expr) => {
+    impl SeedScan for $t {
+      fn scan_two_clip(src1: &[Self], src2: &[Self], lower: Self, out: &mut Vec<usize>) {
+        let lower_v = <$simd>::splat(lower);
+        let chunks = src1.len() / $lanes;
+
+        for c in 0..chunks {
+          let base = c * $lanes;
+          let a = <$simd>::new(src1[base..base + $lanes].try_into().unwrap());
+          let b = <$simd>::new(src2[base..base + $lanes].try_into().unwrap());
+          let mask = (a.simd_gt(lower_v) & b.simd_gt(lower_v)).to_bitmask();
+
+          for lane in 0..$lanes {
+            if mask & (1 << lane) != 0 {
+              out.push(base + lane);
+            }
+          }
+        }
+
+        for (i, (&s1, &s2)) in src1.iter().zip(src2).enumerate().skip(chunks * $lanes) {
+          if s1 > lower && s2 > lower {
+            out.push(i);
+          }
+        }
+      }
+
+      fn scan_threshold(src1: &[Self], th_hi: Self, out: &mut Vec<usize>) {
+        let th_hi_v = <$simd>::splat(th_hi);
+        let chunks = src1.len() / $lanes;
+
+        for c in 0..chunks {
+          let base = c * $lanes;
+          let a = <$simd>::new(src1[base..base + $lanes].try_into().unwrap());
+          let mask = a.simd_ge(th_hi_v).to_bitmask();
+
+          for lane in 0..$lanes {
+            if mask & (1 << lane) != 0 {
+              out.push(base + lane);
+            }
+          }
+        }
+
+        for (i, &s1) in src1.iter().enumerate().skip(chunks * $lanes) {
+          if s1 >= th_hi {
+            out.push(i);
+          }
+        }
+      }
+    }
+  };
+}
+
+impl_seed_scan!(u8, u8x16, 16);
+impl_seed_scan!(u16, u16x8, 8);
+impl_seed_scan!(f32, f32x4, 4);
+
+/// Connected-component statistics accumulated across the processed planes of
+/// a frame, written out as frame properties by [`Filter::get_frame`].
+#[derive(Default)]
+struct ComponentStats {
+  /// Number of connected components copied from `clipb` (or seeded in
+  /// threshold mode).
+  component_count: i64,
+
+  /// Total number of pixels set to `peak` by the flood fill.
+  grown_area: i64,
+
+  /// Total number of pixels across the processed planes.
+  total_pixels: i64,
+}
+
 impl HysteresisFilter {
   fn process_frame<T>(
     &self,
     src1: &VideoFrame,
-    src2: &VideoFrame,
+    src2: Option<&VideoFrame>,
     dst: &mut VideoFrame,
     format: &VideoFormat,
-  ) where
-    T: Copy + From<u8> + NumCast + PartialOrd,
+  ) -> ComponentStats
+  where
+    T: Copy + From<u8> + NumCast + PartialOrd + SeedScan,
   {
     let (lower, upper): (T, T) = (
       <T as NumCast>::from(0).unwrap(),
       <T as NumCast>::from(self.peak).unwrap(),
     );
 
-    let mut visited = HashSet::<i32>::new();
+    let max_plane_pixels = (0..format.num_planes)
+      .map(|plane| (src1.frame_width(plane) as usize) * (src1.frame_height(plane) as usize))
+      .max()
+      .unwrap_or(0);
+
+    // Reused across planes (cleared rather than reallocated) since the
+    // flood fill and seed scan are the hottest part of this filter.
+    let mut visited = vec![false; max_plane_pixels];
+    let mut coords = Vec::<(i32, i32)>::new();
+    let mut seed_indices = Vec::<usize>::new();
+    let mut stats = ComponentStats::default();
 
     for plane in (0..format.num_planes).filter(|&plane| self.process_planes[plane as usize]) {
       let width = src1.frame_width(plane);
       let height = src1.frame_height(plane);
+      let pixel_count = (width * height) as usize;
       let src1_slice = src1.as_slice::<T>(plane);
-      let src2_slice = src2.as_slice::<T>(plane);
       let dst_slice = dst.as_mut_slice::<T>(plane);
 
       dst_slice.fill(lower);
+      visited[..pixel_count].fill(false);
+      coords.clear();
+      seed_indices.clear();
+
+      let is_growable: Predicate<'_> = match &self.mode {
+        Mode::TwoClip => {
+          let src2_slice = src2
+            .expect("clipb is required in two-clip mode")
+            .as_slice::<T>(plane);
+
+          // `as_slice` covers the full strided plane buffer, which can be
+          // wider than `width` once row padding is accounted for; only scan
+          // the first `width * height` samples, indexed as `y * width + x`
+          // like the flood fill below, so seed offsets stay within
+          // `visited`'s bounds.
+          T::scan_two_clip(
+            &src1_slice[..pixel_count],
+            &src2_slice[..pixel_count],
+            lower,
+            &mut seed_indices,
+          );
+
+          Box::new(move |i| src2_slice[i] > lower)
+        }
+        Mode::Threshold { th_lo, th_hi } => {
+          // Integer samples only hold whole values, so round the thresholds
+          // up to keep `>= th_lo` and `>= th_hi` exact after the cast.
+          let (th_lo, th_hi) = if format.sample_type == SampleType::Integer {
+            (th_lo.ceil(), th_hi.ceil())
+          } else {
+            (*th_lo, *th_hi)
+          };
+          let th_lo: T = <T as NumCast>::from(th_lo).unwrap();
+          let th_hi: T = <T as NumCast>::from(th_hi).unwrap();
+
+          T::scan_threshold(&src1_slice[..pixel_count], th_hi, &mut seed_indices);
+
+          Box::new(move |i| src1_slice[i] >= th_lo)
+        }
+      };
 
-      let mut coords = Vec::<(i32, i32)>::new();
+      stats.total_pixels += pixel_count as i64;
 
-      for (i, (_, _)) in src1_slice
-        .iter()
-        .zip(src2_slice.iter())
-        .enumerate()
-        .filter(|&(_, (&src1_val, &src2_val))| src1_val > lower && src2_val > lower)
-      {
-        if !visited.insert(i as i32) {
+      for &i in &seed_indices {
+        if visited[i] {
           continue;
         }
 
+        visited[i] = true;
+        stats.component_count += 1;
         dst_slice[i] = upper;
+        stats.grown_area += 1;
 
         let x = i as i32 % width;
         let y = i as i32 / width;
@@ -133,18 +315,21 @@ impl HysteresisFilter {
           for yy in max(pos.1 - 1, 0)..=min(pos.1 + 1, height - 1) {
             for xx in max(pos.0 - 1, 0)..=min(pos.0 + 1, width - 1) {
               let count = (width * yy + xx) as usize;
-              if visited.contains(&(count as i32)) || src2_slice[count] <= lower {
+              if visited[count] || !is_growable(count) {
                 continue;
               }
 
-              visited.insert(count as i32);
+              visited[count] = true;
               dst_slice[count] = upper;
+              stats.grown_area += 1;
               coords.push((xx, yy));
             }
           }
         }
       }
     }
+
+    stats
   }
 }
 
@@ -162,9 +347,7 @@ impl Filter for HysteresisFilter {
     let Ok(node1) = input.get_video_node(key!(c"clipa"), 0) else {
       return Err(cstr!("Failed to get clipa."));
     };
-    let Ok(node2) = input.get_video_node(key!(c"clipb"), 0) else {
-      return Err(cstr!("Failed to get clipb."));
-    };
+    let node2 = input.get_video_node(key!(c"clipb"), 0).ok();
 
     let n = node1.clone();
     let vi = n.info();
@@ -175,38 +358,99 @@ impl Filter for HysteresisFilter {
       ));
     }
 
-    if !is_same_video_info(vi, node2.info()) {
-      return Err(cstr!(
-        "hysteresis: both clips must have the same dimensions and format"
-      ));
-    }
+    let peak = vi.format.peak_value(None, Some(ColorRange::Full)) as f64;
+
+    let mode = if let Some(node2) = &node2 {
+      if !is_same_video_info(vi, node2.info()) {
+        return Err(cstr!(
+          "hysteresis: both clips must have the same dimensions and format"
+        ));
+      }
+
+      Mode::TwoClip
+    } else {
+      let Ok(th_lo) = input.get_float(key!(c"th_lo"), 0) else {
+        return Err(cstr!(
+          "hysteresis: th_lo and th_hi are required when clipb is not given"
+        ));
+      };
+      let Ok(th_hi) = input.get_float(key!(c"th_hi"), 0) else {
+        return Err(cstr!(
+          "hysteresis: th_lo and th_hi are required when clipb is not given"
+        ));
+      };
+
+      if !(0.0..=peak).contains(&th_lo) || !(0.0..=peak).contains(&th_hi) {
+        return Err(cstr!(
+          "hysteresis: th_lo and th_hi must be finite and within the clip's sample range [0, peak]"
+        ));
+      }
+
+      if th_lo > th_hi {
+        return Err(cstr!("hysteresis: th_lo must not be greater than th_hi"));
+      }
+
+      Mode::Threshold { th_lo, th_hi }
+    };
+
+    let num_frames = if let Some(node2) = &node2 {
+      let length = match input.get_utf8(key!(c"length"), 0) {
+        Ok("shortest") => Length::Shortest,
+        Ok("clipa") | Err(_) => Length::ClipA,
+        Ok("clipb") => Length::ClipB,
+        Ok(_) => {
+          return Err(cstr!(
+            "hysteresis: length must be 'shortest', 'clipa', or 'clipb'"
+          ));
+        }
+      };
+
+      match length {
+        Length::Shortest => vi.num_frames.min(node2.info().num_frames),
+        Length::ClipA => vi.num_frames,
+        Length::ClipB => node2.info().num_frames,
+      }
+    } else {
+      vi.num_frames
+    };
+
+    let out_vi = VideoInfo {
+      num_frames,
+      ..vi.clone()
+    };
 
     let filter = Self {
       node1,
       node2,
-      peak: vi.format.peak_value(None, Some(ColorRange::Full)) as u32,
+      peak: peak as u32,
+      mode,
       process_planes: normalize_planes(&input).expect("Failed to determine places to process."),
     };
 
-    let deps = [
-      FilterDependency {
-        source: filter.node1.as_ptr(),
-        request_pattern: RequestPattern::StrictSpatial,
+    let mut deps = vec![FilterDependency {
+      source: filter.node1.as_ptr(),
+      request_pattern: if filter.node1.info().num_frames == out_vi.num_frames {
+        RequestPattern::StrictSpatial
+      } else {
+        RequestPattern::General
       },
-      FilterDependency {
-        source: filter.node2.as_ptr(),
-        request_pattern: if vi.num_frames <= filter.node2.info().num_frames {
+    }];
+
+    if let Some(node2) = &filter.node2 {
+      deps.push(FilterDependency {
+        source: node2.as_ptr(),
+        request_pattern: if node2.info().num_frames == out_vi.num_frames {
           RequestPattern::StrictSpatial
         } else {
           RequestPattern::General
         },
-      },
-    ];
+      });
+    }
 
     core.create_video_filter(
       output,
       cstr!("Hysteresis"),
-      vi,
+      &out_vi,
       Box::new(filter),
       Dependencies::new(&deps).unwrap(),
     );
@@ -224,12 +468,19 @@ impl Filter for HysteresisFilter {
   ) -> Result<Option<VideoFrame>, Self::Error> {
     match activation_reason {
       ActivationReason::Initial => {
-        ctx.request_frame_filter(n, &self.node1);
-        ctx.request_frame_filter(n, &self.node2);
+        ctx.request_frame_filter(clamp_frame(n, &self.node1), &self.node1);
+        if let Some(node2) = &self.node2 {
+          ctx.request_frame_filter(clamp_frame(n, node2), node2);
+        }
       }
       ActivationReason::AllFramesReady => {
-        let src1 = self.node1.get_frame_filter(n, &mut ctx);
-        let src2 = self.node2.get_frame_filter(n, &mut ctx);
+        let src1 = self
+          .node1
+          .get_frame_filter(clamp_frame(n, &self.node1), &mut ctx);
+        let src2 = self
+          .node2
+          .as_ref()
+          .map(|node2| node2.get_frame_filter(clamp_frame(n, node2), &mut ctx));
 
         let fi = src1.get_video_format();
 
@@ -250,12 +501,42 @@ impl Filter for HysteresisFilter {
           Some(&src1),
         );
 
-        if fi.bytes_per_sample == 1 {
-          self.process_frame::<u8>(&src1, &src2, &mut dst, fi);
+        let stats = if fi.bytes_per_sample == 1 {
+          self.process_frame::<u8>(&src1, src2.as_ref(), &mut dst, fi)
         } else if fi.bytes_per_sample == 2 {
-          self.process_frame::<u16>(&src1, &src2, &mut dst, fi);
+          self.process_frame::<u16>(&src1, src2.as_ref(), &mut dst, fi)
         } else {
-          self.process_frame::<f32>(&src1, &src2, &mut dst, fi);
+          self.process_frame::<f32>(&src1, src2.as_ref(), &mut dst, fi)
+        };
+
+        if let Some(mut props) = dst.properties_mut() {
+          let coverage = if stats.total_pixels > 0 {
+            stats.grown_area as f64 / stats.total_pixels as f64
+          } else {
+            0.0
+          };
+
+          props
+            .set(
+              key!(c"HysteresisComponentCount"),
+              Value::Int(stats.component_count),
+              AppendMode::Replace,
+            )
+            .expect("Failed to set HysteresisComponentCount.");
+          props
+            .set(
+              key!(c"HysteresisGrownArea"),
+              Value::Int(stats.grown_area),
+              AppendMode::Replace,
+            )
+            .expect("Failed to set HysteresisGrownArea.");
+          props
+            .set(
+              key!(c"HysteresisCoverage"),
+              Value::Float(coverage),
+              AppendMode::Replace,
+            )
+            .expect("Failed to set HysteresisCoverage.");
         }
 
         return Ok(Some(dst));
@@ -267,7 +548,191 @@ impl Filter for HysteresisFilter {
   }
 
   const NAME: &'static CStr = cstr!("Hysteresis");
-  const ARGS: &'static CStr = cstr!("clipa:vnode;clipb:vnode;planes:int[]:opt;");
+  const ARGS: &'static CStr = cstr!(
+    "clipa:vnode;clipb:vnode:opt;th_lo:float:opt;th_hi:float:opt;planes:int[]:opt;length:data:opt;"
+  );
+  const RETURN_TYPE: &'static CStr = cstr!("clip:vnode;");
+}
+
+/// Size of the lookup table [`AdaptiveMaskFilter`] builds per frame to avoid
+/// calling `powf` for every pixel.
+const MASK_LUT_SIZE: usize = 1024;
+
+/// Evaluates adaptivegrain's luma-adaptive curve at a normalized pixel value
+/// `x` and normalized frame-average luma `y`.
+fn adaptive_mask_curve(x: f64, y: f64, luma_scaling: f64) -> f64 {
+  let poly = x * (1.124 + x * (-9.466 + x * (36.624 + x * (-45.47 + x * 18.188))));
+
+  // The polynomial can exceed 1 near the edges of the curve, which would
+  // otherwise raise a negative base to a fractional power and produce NaN.
+  let base = (1.0 - poly).max(0.0);
+
+  base.powf(y * y * luma_scaling).clamp(0.0, 1.0)
+}
+
+/// Generates a GRAY mask from a clip's luma using the curve adaptivegrain
+/// uses to scale its grain by frame brightness: for each pixel, `x` is the
+/// normalized pixel value in `[0, 1]` and `y` is the normalized frame-average
+/// luma read from the `PlaneStatsAverage` frame property (run
+/// `std.PlaneStats` on `clip` first). The mask is `(1 - poly(x))^(y^2 *
+/// luma_scaling)` clamped to `[0, 1]` and scaled to the output peak. Piping
+/// this into [`HysteresisFilter`] gives a complete mask-to-hysteresis
+/// pipeline without needing another plugin to build the masks.
+struct AdaptiveMaskFilter {
+  node: VideoNode,
+
+  /// Peak value shared by the input and the GRAY output, since the output
+  /// format matches the input's sample type and bit depth.
+  peak: u32,
+
+  luma_scaling: f64,
+}
+
+impl AdaptiveMaskFilter {
+  fn write_frame<T>(&self, src: &VideoFrame, y: f64, dst: &mut VideoFrame)
+  where
+    T: Copy + From<u8> + NumCast,
+  {
+    let is_integer = src.get_video_format().sample_type == SampleType::Integer;
+    let mut lut = [<T as NumCast>::from(0).unwrap(); MASK_LUT_SIZE];
+
+    for (i, entry) in lut.iter_mut().enumerate() {
+      let x = i as f64 / (MASK_LUT_SIZE - 1) as f64;
+      let mask = adaptive_mask_curve(x, y, self.luma_scaling);
+      let scaled = mask * self.peak as f64;
+      *entry = <T as NumCast>::from(if is_integer { scaled.round() } else { scaled }).unwrap();
+    }
+
+    let src_slice = src.as_slice::<T>(0);
+    let dst_slice = dst.as_mut_slice::<T>(0);
+
+    for (d, &s) in dst_slice.iter_mut().zip(src_slice) {
+      let x = <f64 as NumCast>::from(s).unwrap() / self.peak as f64;
+      let index = ((x * (MASK_LUT_SIZE - 1) as f64).round() as usize).min(MASK_LUT_SIZE - 1);
+      *d = lut[index];
+    }
+  }
+}
+
+impl Filter for AdaptiveMaskFilter {
+  type Error = &'static CStr;
+  type FrameType = VideoFrame;
+  type FilterData = ();
+
+  fn create(
+    input: MapRef<'_>,
+    output: MapRef<'_>,
+    _data: Option<Box<Self::FilterData>>,
+    mut core: CoreRef<'_>,
+  ) -> Result<(), Self::Error> {
+    let Ok(node) = input.get_video_node(key!(c"clip"), 0) else {
+      return Err(cstr!("Failed to get clip."));
+    };
+
+    let vi = node.info();
+
+    if !is_constant_video_format(vi) || !is_8_to_16_or_float_format(&vi.format) {
+      return Err(cstr!(
+        "AdaptiveMask: only constant format 8-16 bits integer and 32 bits float input supported"
+      ));
+    }
+
+    let luma_scaling = input.get_float(key!(c"luma_scaling"), 0).unwrap_or(8.0);
+
+    if !luma_scaling.is_finite() || luma_scaling < 0.0 {
+      return Err(cstr!(
+        "AdaptiveMask: luma_scaling must be a finite, non-negative number"
+      ));
+    }
+
+    let out_format = core.query_video_format(
+      ColorFamily::Gray,
+      vi.format.sample_type,
+      vi.format.bits_per_sample,
+      0,
+      0,
+    );
+
+    let out_vi = VideoInfo {
+      format: out_format,
+      ..vi.clone()
+    };
+
+    let filter = Self {
+      peak: vi.format.peak_value(None, Some(ColorRange::Full)) as u32,
+      luma_scaling,
+      node,
+    };
+
+    let deps = [FilterDependency {
+      source: filter.node.as_ptr(),
+      request_pattern: RequestPattern::StrictSpatial,
+    }];
+
+    core.create_video_filter(
+      output,
+      cstr!("AdaptiveMask"),
+      &out_vi,
+      Box::new(filter),
+      Dependencies::new(&deps).unwrap(),
+    );
+
+    Ok(())
+  }
+
+  fn get_frame(
+    &self,
+    n: i32,
+    activation_reason: ActivationReason,
+    _frame_data: *mut *mut c_void,
+    mut ctx: FrameContext,
+    core: CoreRef<'_>,
+  ) -> Result<Option<VideoFrame>, Self::Error> {
+    match activation_reason {
+      ActivationReason::Initial => {
+        ctx.request_frame_filter(n, &self.node);
+      }
+      ActivationReason::AllFramesReady => {
+        let src = self.node.get_frame_filter(n, &mut ctx);
+
+        let Some(y) = src
+          .properties()
+          .and_then(|props| props.get_float(key!(c"PlaneStatsAverage"), 0).ok())
+        else {
+          return Err(cstr!(
+            "AdaptiveMask: clip is missing the PlaneStatsAverage frame property, run std.PlaneStats first"
+          ));
+        };
+
+        let fi = src.get_video_format();
+        let out_format =
+          core.query_video_format(ColorFamily::Gray, fi.sample_type, fi.bits_per_sample, 0, 0);
+
+        let mut dst = core.new_video_frame(
+          &out_format,
+          src.frame_width(0),
+          src.frame_height(0),
+          Some(&src),
+        );
+
+        if fi.bytes_per_sample == 1 {
+          self.write_frame::<u8>(&src, y, &mut dst);
+        } else if fi.bytes_per_sample == 2 {
+          self.write_frame::<u16>(&src, y, &mut dst);
+        } else {
+          self.write_frame::<f32>(&src, y, &mut dst);
+        }
+
+        return Ok(Some(dst));
+      }
+      ActivationReason::Error => {}
+    }
+
+    Ok(None)
+  }
+
+  const NAME: &'static CStr = cstr!("AdaptiveMask");
+  const ARGS: &'static CStr = cstr!("clip:vnode;luma_scaling:float:opt;");
   const RETURN_TYPE: &'static CStr = cstr!("clip:vnode;");
 }
 
@@ -278,5 +743,6 @@ declare_plugin!(
   (1, 0),
   VAPOURSYNTH_API_VERSION,
   0,
-  (HysteresisFilter, None)
+  (HysteresisFilter, None),
+  (AdaptiveMaskFilter, None)
 );